@@ -12,6 +12,11 @@ use leptos_router::{
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::codec::Codec;
+use crate::diagnostic::{Diagnostic, IntoDiagnostic, Label, Severity};
+use crate::protected_route::ProtectedRoute;
+use crate::transition::Transition;
+
 #[component]
 pub fn App() -> impl IntoView {
     // Provides context that manages stylesheets, titles, meta tags, etc.
@@ -46,6 +51,30 @@ pub fn App() -> impl IntoView {
                         view=Post
                         ssr=SsrMode::InOrder
                     />
+
+                    // There's still no `SsrMode::Static` to put here -- that'd have to live in
+                    // `leptos_router` itself -- so `/post` stays on `SsrMode::Async` above.
+                    // `get_post_cached` below memoizes the post fetch behind `crate::fetch_cache`
+                    // (revalidating after a minute), and the admin page has a button that calls
+                    // `warm_post_cache` to pre-fetch every known post id up front. That only
+                    // skips `get_post`'s simulated delay on a hit, though -- the component
+                    // subtree (`Suspense`/`Transition`/etc.) still runs every request, same as
+                    // it would with a plain `SsrMode::Async` route.
+
+                    // An auth-gated route: the guard runs as part of the page's async rendering,
+                    // so a non-admin visitor gets redirected before anything protected is ever
+                    // streamed down.
+                    <Route
+                        path=StaticSegment("admin")
+                        view=|| view! {
+                            <ProtectedRoute
+                                condition=|| async move { is_admin().await.unwrap_or(false) }
+                                redirect_path="/"
+                                view=AdminPage
+                            />
+                        }
+                        ssr=SsrMode::Async
+                    />
                 </FlatRoutes>
             </main>
         </Router>
@@ -56,22 +85,36 @@ pub fn App() -> impl IntoView {
 fn HomePage() -> impl IntoView {
     // a variety of different encodings can now be specified per Resource
     // it defaults to a FromStr/Display-based encoding -- maybe serde_json should be the default,
-    // but it's not for now 
-    // 
+    // but it's not for now
+    //
     // this could be Resource::new_rkyv() etc. (the encodings are enabled by different feature
     // flags on leptos_server)
-    let posts = Resource::new_serde(|| (), |_| list_post_metadata());
+    //
+    // There's still no `Resource::new_with_codec`, so rather than leaving this on a plain
+    // `new_serde` (untouched JSON, and the list here is exactly the "can grow large" case that
+    // wants something more compact), we run it through `crate::codec::JsonCodec` by hand: the
+    // resource's value is now the already-encoded-and-`<script>`-escaped string, decoded back
+    // into `Vec<PostMetadata>` wherever it's read below. Swapping `JsonCodec` for
+    // `MsgPackCodec`/`CborCodec`/`BitcodeCodec` (behind their feature flags) is then just a
+    // change to the three `JsonCodec` calls in this component.
+    let posts = Resource::new_serde(|| (), |_| async move {
+        list_post_metadata()
+            .await
+            .map(|metadata| crate::codec::JsonCodec::encode(&metadata).unwrap_or_default())
+    });
 
-    // Suspense now works directly with individual Futures 
+    // Suspense now works directly with individual Futures
     // you can Suspend one or more Futures within an Suspense component
     //
     // in 0.1-0.6, Suspense runs its whole body once initially to register resource reads, and so
-    // you need to check whether a resource is None 
-    // 
+    // you need to check whether a resource is None
+    //
     // in 0.7, you can just .await the actual value of the resource
     let posts_view = move || Suspend(async move {
-        posts.await.map(|posts| {
-            posts.into_iter()
+        posts.await.map(|encoded| {
+            crate::codec::JsonCodec::decode::<Vec<PostMetadata>>(&encoded)
+                .unwrap_or_default()
+                .into_iter()
                 .map(|post| view! {
                     <li>
                         <a href=format!("/post/{}", post.id)>{post.title.clone()}</a>
@@ -86,8 +129,12 @@ fn HomePage() -> impl IntoView {
     // resources can wait for values from other resources
     // TBH I'm not sure whether this needs to explicitly track `posts` (I think it does, with the
     // way hydration of resources is at the moment). Feel free to play around.
-    let post_count = Resource::new(move || posts.track(), move |_| async move { 
-        posts.await.unwrap_or_default().len()
+    let post_count = Resource::new(move || posts.track(), move |_| async move {
+        posts.await
+            .ok()
+            .and_then(|encoded| crate::codec::JsonCodec::decode::<Vec<PostMetadata>>(&encoded).ok())
+            .map(|posts| posts.len())
+            .unwrap_or_default()
     });
 
     view! {
@@ -116,19 +163,30 @@ fn Post() -> impl IntoView {
             .map(|q| q.id.unwrap_or_default())
             .map_err(|_| PostError::InvalidId)
     };
+    // A post's `content` is free-text -- nothing stops it from containing the literal bytes
+    // `</script><script>alert(1)</script>` -- so, same as `posts` in `HomePage`, this resource's
+    // value is the `JsonCodec`-encoded (and so `<script>`-breakout-escaped) string, not the
+    // `Result<Post, PostError>` directly. `post_view` below decodes it back out.
     let post_resource = Resource::new_serde(id, |id| async move {
-        match id {
+        let result: Result<Post, PostError> = match id {
             Err(e) => Err(e),
-            Ok(id) => get_post(id)
+            Ok(id) => get_post_cached(id)
                 .await
                 .map(|data| data.ok_or(PostError::PostNotFound))
-                .map_err(|_| PostError::ServerError),
-        }
+                .map_err(|_| PostError::ServerError)
+                .and_then(std::convert::identity),
+        };
+        crate::codec::JsonCodec::encode(&result).unwrap_or_default()
     });
 
-    let post_view = Suspend(async move {
-        match post_resource.await {
-            Ok(Ok(post)) => Ok(view! {
+    // `<Transition>` reads `post_resource` synchronously (like `<Suspense>` normally would,
+    // just without the `.await`), so navigating from `/post/1` to `/post/2` keeps showing post
+    // 1's content -- instead of flashing back to "Loading post..." -- until post 2 arrives.
+    let post_view = move |encoded: String| {
+        let result: Result<Post, PostError> = crate::codec::JsonCodec::decode(&encoded)
+            .unwrap_or(Err(PostError::ServerError));
+        match result {
+            Ok(post) => Ok(view! {
                 <h1>{post.title.clone()}</h1>
                 <p>{post.content.clone()}</p>
 
@@ -138,14 +196,15 @@ fn Post() -> impl IntoView {
                 <Title text=post.title/>
                 <Meta name="description" content=post.content/>
             }),
-            _ => Err(PostError::ServerError),
+            Err(e) => Err(e),
         }
-    });
+    };
 
     view! {
         <em>"The world's best content."</em>
-        <Suspense fallback=move || view! { <p>"Loading post..."</p> }>
-            <ErrorBoundary fallback=|errors| {
+        <Transition resource=post_resource fallback=move || view! { <p>"Loading post..."</p> }>
+            {move |encoded| view! {
+                <ErrorBoundary fallback=|errors| {
                 view! {
                     // You'll notice the type of `errors` here is ArcRwSignal, and it's Clone but not
                     // Copy. We only need to use it once, so this is fine, but if we needed it multiple
@@ -162,18 +221,85 @@ fn Post() -> impl IntoView {
                                 errors
                                     .get()
                                     .into_iter()
-                                    .map(|(_, error)| view! { <li>{error.to_string()}</li> })
+                                    .map(|(_, error)| {
+                                        // `errors` only ever holds `PostError`s here, and only
+                                        // `PostError` is downcast; any other error type still
+                                        // renders, just without a code/help -- same as
+                                        // `error.to_string()` did before.
+                                        let diagnostic = error
+                                            .downcast_ref::<PostError>()
+                                            .map(IntoDiagnostic::into_diagnostic)
+                                            .unwrap_or_else(|| Diagnostic::from_display(&error));
+                                        view! {
+                                            <li class=format!(
+                                                "severity-{}",
+                                                diagnostic.severity.as_str(),
+                                            )>
+                                                {diagnostic
+                                                    .code
+                                                    .clone()
+                                                    .map(|code| view! { <code>{code}</code> " " })}
+                                                {diagnostic.message.clone()}
+                                                {diagnostic
+                                                    .help
+                                                    .clone()
+                                                    .map(|help| view! { <p class="help">{help}</p> })}
+                                                {(!diagnostic.labels.is_empty())
+                                                    .then(|| {
+                                                        view! {
+                                                            <ul class="labels">
+                                                                {diagnostic
+                                                                    .labels
+                                                                    .iter()
+                                                                    .map(|label| {
+                                                                        view! {
+                                                                            <li>
+                                                                                <code>{label.label.clone()}</code>
+                                                                                ": "
+                                                                                {label.span.clone()}
+                                                                            </li>
+                                                                        }
+                                                                    })
+                                                                    .collect::<Vec<_>>()}
+                                                            </ul>
+                                                        }
+                                                    })}
+                                            </li>
+                                        }
+                                    })
                                     .collect::<Vec<_>>()
                             }}
 
                         </ul>
                     </div>
                 }
-            }>{post_view}</ErrorBoundary>
-        </Suspense>
+                }>{post_view(encoded)}</ErrorBoundary>
+            }}
+        </Transition>
     }
 }
 
+#[component]
+fn AdminPage() -> impl IntoView {
+    let warm_cache = ServerAction::<WarmPostCache>::new();
+
+    view! {
+        <h1>"Admin dashboard"</h1>
+        <button on:click=move |_| {
+            warm_cache.dispatch(WarmPostCache {});
+        }>
+            "Pre-fetch all posts"
+        </button>
+    }
+}
+
+/// Stands in for a real session check -- a production app would look at the request's auth
+/// cookie/token here instead of always granting access.
+#[server]
+pub async fn is_admin() -> Result<bool, ServerFnError> {
+    Ok(true)
+}
+
 // Dummy API
 lazy_static! {
     static ref POSTS: Vec<Post> = vec![
@@ -205,6 +331,36 @@ pub enum PostError {
     ServerError,
 }
 
+impl IntoDiagnostic for PostError {
+    fn into_diagnostic(&self) -> Diagnostic {
+        match self {
+            PostError::InvalidId => Diagnostic {
+                message: self.to_string(),
+                code: Some("POST_INVALID_ID".to_string()),
+                severity: Severity::Error,
+                help: Some("Post ids are non-negative integers, e.g. /post/1.".to_string()),
+                labels: vec![Label {
+                    label: "id".to_string(),
+                    span: "/post/:id".to_string(),
+                }],
+            },
+            PostError::PostNotFound => Diagnostic {
+                message: self.to_string(),
+                code: Some("POST_NOT_FOUND".to_string()),
+                severity: Severity::Warning,
+                help: Some(
+                    "Check the home page for the list of posts that currently exist.".to_string(),
+                ),
+                labels: vec![Label {
+                    label: "id".to_string(),
+                    span: "/post/:id".to_string(),
+                }],
+            },
+            PostError::ServerError => Diagnostic::from_display(self),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Post {
     id: usize,
@@ -235,3 +391,91 @@ pub async fn get_post(id: usize) -> Result<Option<Post>, ServerFnError> {
     tokio::time::sleep(std::time::Duration::from_secs(1)).await;
     Ok(POSTS.iter().find(|post| post.id == id).cloned())
 }
+
+/// The post ids a `/post/:id` static-params provider would enumerate ahead of time, so every
+/// known post can be pre-fetched via [`crate::fetch_cache::prewarm_known_fetches`] instead of
+/// paying the fetch cost on whichever request happens to hit a given id first.
+#[cfg(feature = "ssr")]
+pub fn known_post_ids() -> Vec<usize> {
+    POSTS.iter().map(|post| post.id).collect()
+}
+
+/// Fetches and serializes post `id` -- the one bit of work both `get_post_cached` (on a cache
+/// miss) and `warm_post_cache` need, so it isn't pasted twice.
+#[cfg(feature = "ssr")]
+async fn fetch_post_payload(id: usize) -> String {
+    let post = get_post(id).await.ok().flatten();
+    serde_json::to_string(&post).unwrap_or_default()
+}
+
+/// What `Post` actually calls instead of `get_post` directly -- same signature, but backed by
+/// `crate::fetch_cache`'s memoized result rather than hitting the "database" on every request.
+#[server]
+pub async fn get_post_cached(id: usize) -> Result<Option<Post>, ServerFnError> {
+    let payload = crate::fetch_cache::memoize_fetch(
+        &format!("/post/{id}"),
+        crate::fetch_cache::Revalidate::After(std::time::Duration::from_secs(60)),
+        move || fetch_post_payload(id),
+    )
+    .await;
+    Ok(serde_json::from_str(&payload).unwrap_or_default())
+}
+
+/// Pre-fetches every known post up front, so the cache is already warm by the time real
+/// requests for any of them arrive. Wired up to the button on `/admin`.
+#[server]
+pub async fn warm_post_cache() -> Result<(), ServerFnError> {
+    crate::fetch_cache::prewarm_known_fetches(
+        known_post_ids(),
+        |id| format!("/post/{id}"),
+        |id| fetch_post_payload(*id),
+    )
+    .await;
+    Ok(())
+}
+
+/// Called by whatever eventually mutates a post's data, so the cached `/post/:id` entry (if one
+/// exists) gets re-fetched on the next request instead of serving stale content indefinitely.
+#[server]
+pub async fn invalidate_post(id: usize) -> Result<(), ServerFnError> {
+    crate::fetch_cache::invalidate_cached_fetch(&format!("/post/{id}"));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_id_diagnostic_is_populated() {
+        let diagnostic = PostError::InvalidId.into_diagnostic();
+
+        assert_eq!(diagnostic.code, Some("POST_INVALID_ID".to_string()));
+        assert_eq!(diagnostic.severity, Severity::Error);
+        assert!(diagnostic.help.is_some());
+        assert_eq!(diagnostic.labels.len(), 1);
+        assert_eq!(diagnostic.labels[0].span, "/post/:id");
+    }
+
+    #[test]
+    fn post_not_found_diagnostic_is_populated() {
+        let diagnostic = PostError::PostNotFound.into_diagnostic();
+
+        assert_eq!(diagnostic.code, Some("POST_NOT_FOUND".to_string()));
+        assert_eq!(diagnostic.severity, Severity::Warning);
+        assert!(diagnostic.help.is_some());
+        assert_eq!(diagnostic.labels.len(), 1);
+        assert_eq!(diagnostic.labels[0].span, "/post/:id");
+    }
+
+    #[test]
+    fn server_error_diagnostic_falls_back_to_display_only() {
+        let diagnostic = PostError::ServerError.into_diagnostic();
+
+        assert_eq!(diagnostic.message, PostError::ServerError.to_string());
+        assert_eq!(diagnostic.code, None);
+        assert_eq!(diagnostic.severity, Severity::Error);
+        assert_eq!(diagnostic.help, None);
+        assert!(diagnostic.labels.is_empty());
+    }
+}
@@ -0,0 +1,200 @@
+//! Pluggable (de)serialization for values shipped from the server to the client.
+//!
+//! There's still no real `Resource::new_with_codec` -- that'd need to live in `leptos_server` --
+//! but `HomePage`'s `posts` resource in `app.rs` is wired up to `JsonCodec` by hand (encode
+//! before the resource stores the value, decode everywhere it's read), so this isn't just
+//! scaffolding sitting next to the thing it's supposed to replace. `MsgPackCodec`/`CborCodec`/
+//! `BitcodeCodec` are the same shape behind their own feature flags, for when JSON's size stops
+//! being good enough. Every codec runs its output through
+//! [`crate::html_escape::escape_script_breakout`] -- binary formats get base64-wrapped in a JSON
+//! string first, so the escaping still applies uniformly even though base64 itself has nothing
+//! HTML-significant in it.
+
+use serde::{de::DeserializeOwned, Serialize};
+use thiserror::Error;
+
+use crate::html_escape::escape_script_breakout;
+
+#[derive(Debug, Error)]
+pub enum CodecError {
+    #[error("failed to encode value: {0}")]
+    Encode(String),
+    #[error("failed to decode value: {0}")]
+    Decode(String),
+}
+
+/// A serialization format usable for a resource's SSR-to-hydration payload, or for a server
+/// function's request/response body.
+///
+/// The same codec has to be used for both the SSR encode and the client-side decode for
+/// hydration to succeed, so a resource and the server function it wraps should agree on one
+/// `Codec` implementation.
+pub trait Codec {
+    /// The MIME type this codec produces; usable as the `Content-Type` for a server function
+    /// body encoded with it.
+    const CONTENT_TYPE: &'static str;
+
+    /// Encodes `value` to text that's safe to inline into an HTML `<script>` element.
+    fn encode<T: Serialize>(value: &T) -> Result<String, CodecError>;
+
+    fn decode<T: DeserializeOwned>(encoded: &str) -> Result<T, CodecError>;
+}
+
+/// The default codec -- matches what `Resource::new_serde` already does, just with the
+/// `<script>`-breakout escaping applied.
+pub struct JsonCodec;
+
+impl Codec for JsonCodec {
+    const CONTENT_TYPE: &'static str = "application/json";
+
+    fn encode<T: Serialize>(value: &T) -> Result<String, CodecError> {
+        let json = serde_json::to_string(value).map_err(|e| CodecError::Encode(e.to_string()))?;
+        Ok(escape_script_breakout(&json))
+    }
+
+    fn decode<T: DeserializeOwned>(encoded: &str) -> Result<T, CodecError> {
+        serde_json::from_str(encoded).map_err(|e| CodecError::Decode(e.to_string()))
+    }
+}
+
+#[cfg(feature = "msgpack")]
+pub struct MsgPackCodec;
+
+#[cfg(feature = "msgpack")]
+impl Codec for MsgPackCodec {
+    const CONTENT_TYPE: &'static str = "application/msgpack";
+
+    fn encode<T: Serialize>(value: &T) -> Result<String, CodecError> {
+        let bytes = rmp_serde::to_vec(value).map_err(|e| CodecError::Encode(e.to_string()))?;
+        encode_binary(&bytes)
+    }
+
+    fn decode<T: DeserializeOwned>(encoded: &str) -> Result<T, CodecError> {
+        let bytes = decode_binary(encoded)?;
+        rmp_serde::from_slice(&bytes).map_err(|e| CodecError::Decode(e.to_string()))
+    }
+}
+
+#[cfg(feature = "cbor")]
+pub struct CborCodec;
+
+#[cfg(feature = "cbor")]
+impl Codec for CborCodec {
+    const CONTENT_TYPE: &'static str = "application/cbor";
+
+    fn encode<T: Serialize>(value: &T) -> Result<String, CodecError> {
+        let mut bytes = Vec::new();
+        serde_cbor::to_writer(&mut bytes, value).map_err(|e| CodecError::Encode(e.to_string()))?;
+        encode_binary(&bytes)
+    }
+
+    fn decode<T: DeserializeOwned>(encoded: &str) -> Result<T, CodecError> {
+        let bytes = decode_binary(encoded)?;
+        serde_cbor::from_slice(&bytes).map_err(|e| CodecError::Decode(e.to_string()))
+    }
+}
+
+#[cfg(feature = "bitcode")]
+pub struct BitcodeCodec;
+
+#[cfg(feature = "bitcode")]
+impl Codec for BitcodeCodec {
+    const CONTENT_TYPE: &'static str = "application/x-bitcode";
+
+    fn encode<T: Serialize>(value: &T) -> Result<String, CodecError> {
+        let bytes =
+            bitcode::serialize(value).map_err(|e| CodecError::Encode(e.to_string()))?;
+        encode_binary(&bytes)
+    }
+
+    fn decode<T: DeserializeOwned>(encoded: &str) -> Result<T, CodecError> {
+        let bytes = decode_binary(encoded)?;
+        bitcode::deserialize(&bytes).map_err(|e| CodecError::Decode(e.to_string()))
+    }
+}
+
+/// Shared by every binary codec: base64 the bytes, then JSON-string-encode (and
+/// `<script>`-escape) the base64 text, since a resource payload is always inlined as text.
+#[cfg(any(feature = "msgpack", feature = "cbor", feature = "bitcode"))]
+fn encode_binary(bytes: &[u8]) -> Result<String, CodecError> {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    let base64 = STANDARD.encode(bytes);
+    let json = serde_json::to_string(&base64).map_err(|e| CodecError::Encode(e.to_string()))?;
+    Ok(escape_script_breakout(&json))
+}
+
+#[cfg(any(feature = "msgpack", feature = "cbor", feature = "bitcode"))]
+fn decode_binary(encoded: &str) -> Result<Vec<u8>, CodecError> {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    let base64: String =
+        serde_json::from_str(encoded).map_err(|e| CodecError::Decode(e.to_string()))?;
+    STANDARD
+        .decode(base64)
+        .map_err(|e| CodecError::Decode(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Sample {
+        id: usize,
+        title: String,
+    }
+
+    fn sample() -> Sample {
+        Sample {
+            id: 7,
+            title: "</script><script>alert(1)</script>".to_string(),
+        }
+    }
+
+    #[test]
+    fn json_codec_round_trips() {
+        let encoded = JsonCodec::encode(&sample()).unwrap();
+        assert!(!encoded.contains("</script>"));
+        let decoded: Sample = JsonCodec::decode(&encoded).unwrap();
+        assert_eq!(decoded, sample());
+    }
+
+    #[test]
+    fn json_codec_rejects_trailing_garbage() {
+        let encoded = JsonCodec::encode(&sample()).unwrap();
+        let tampered = format!("{encoded}trailing-garbage");
+        assert!(JsonCodec::decode::<Sample>(&tampered).is_err());
+    }
+
+    #[cfg(any(feature = "msgpack", feature = "cbor", feature = "bitcode"))]
+    #[test]
+    fn decode_binary_rejects_trailing_garbage_after_the_json_string() {
+        let wrapped = encode_binary(b"hello").unwrap();
+        let tampered = format!("{wrapped}trailing-garbage");
+        assert!(decode_binary(&tampered).is_err());
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[test]
+    fn msgpack_codec_round_trips() {
+        let encoded = MsgPackCodec::encode(&sample()).unwrap();
+        let decoded: Sample = MsgPackCodec::decode(&encoded).unwrap();
+        assert_eq!(decoded, sample());
+    }
+
+    #[cfg(feature = "cbor")]
+    #[test]
+    fn cbor_codec_round_trips() {
+        let encoded = CborCodec::encode(&sample()).unwrap();
+        let decoded: Sample = CborCodec::decode(&encoded).unwrap();
+        assert_eq!(decoded, sample());
+    }
+
+    #[cfg(feature = "bitcode")]
+    #[test]
+    fn bitcode_codec_round_trips() {
+        let encoded = BitcodeCodec::encode(&sample()).unwrap();
+        let decoded: Sample = BitcodeCodec::decode(&encoded).unwrap();
+        assert_eq!(decoded, sample());
+    }
+}
@@ -0,0 +1,103 @@
+//! Structured diagnostics for errors rendered in an `<ErrorBoundary>`.
+//!
+//! `ErrorBoundary`'s fallback only ever sees `error.to_string()` -- enough for a one-line
+//! message, but not enough for an informative error page. `IntoDiagnostic` lets an error type
+//! opt into richer structured data (an error code, a severity, a help/suggestion string, and
+//! labeled source spans); there's a blanket impl over `&E: std::error::Error` that reproduces
+//! today's behavior (just the `Display` message) for anything that doesn't bother implementing
+//! it directly. The blanket lives on `&E` rather than `E` itself so a type like `PostError` in
+//! `app.rs` can still implement `IntoDiagnostic` for itself with real codes and help text --
+//! implementing the trait for both `E` and `&E` would conflict.
+
+use std::fmt;
+
+/// How serious a diagnostic is -- lets a fallback choose an icon/color without string-matching
+/// the message.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+impl Severity {
+    /// A short identifier for picking an icon/color in a fallback, e.g. as a CSS class suffix.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+        }
+    }
+}
+
+/// A labeled span into whatever the error applies to (a request path, a form field, a line in a
+/// config file, ...).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Label {
+    pub label: String,
+    pub span: String,
+}
+
+/// Structured data an `<ErrorBoundary>` fallback can render beyond a bare message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub code: Option<String>,
+    pub severity: Severity,
+    pub help: Option<String>,
+    pub labels: Vec<Label>,
+}
+
+impl Diagnostic {
+    /// Today's behavior: just the `Display` message, no code/help/labels.
+    pub fn from_display(error: &impl fmt::Display) -> Self {
+        Diagnostic {
+            message: error.to_string(),
+            code: None,
+            severity: Severity::Error,
+            help: None,
+            labels: Vec::new(),
+        }
+    }
+}
+
+/// Implemented by error types that want to surface more than `Display` to an `<ErrorBoundary>`
+/// fallback. See the module docs for why the blanket impl lives on `&E` rather than `E`.
+pub trait IntoDiagnostic {
+    fn into_diagnostic(&self) -> Diagnostic;
+}
+
+impl<E: std::error::Error> IntoDiagnostic for &E {
+    fn into_diagnostic(&self) -> Diagnostic {
+        Diagnostic::from_display(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct PlainError;
+
+    impl fmt::Display for PlainError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "plain error")
+        }
+    }
+
+    impl std::error::Error for PlainError {}
+
+    #[test]
+    fn blanket_impl_falls_back_to_display_only() {
+        let error = PlainError;
+        let diagnostic = (&error).into_diagnostic();
+
+        assert_eq!(diagnostic.message, "plain error");
+        assert_eq!(diagnostic.code, None);
+        assert_eq!(diagnostic.severity, Severity::Error);
+        assert_eq!(diagnostic.help, None);
+        assert!(diagnostic.labels.is_empty());
+    }
+}
@@ -0,0 +1,205 @@
+//! Per-route memoization for a server function's fetch, with a revalidation window.
+//!
+//! This is not page caching: `leptos_router` has no `SsrMode::Static` route variant, no rendered
+//! HTML is stored here, and the component subtree (`Suspense`/`Transition`/`ErrorBoundary`/
+//! `Title`/`Meta`) still reruns on every request. Only a server function's serialized payload
+//! is cached: a `Mutex<HashMap>` memoizing one server
+//! function's result per cache key (e.g. `/post/1`), with [`Revalidate`] controlling how long a
+//! cached result is served before [`memoize_fetch`] re-runs the fetch in the background. See
+//! `get_post_cached`/`warm_post_cache`/`invalidate_post` in `app.rs` for the only real consumer.
+#![cfg(feature = "ssr")]
+
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use lazy_static::lazy_static;
+
+/// One memoized fetch result, keyed by whatever the caller used as its cache key.
+#[derive(Clone, Debug)]
+struct CachedFetch {
+    payload: String,
+    fetched_at: Instant,
+}
+
+/// Revalidation policy for a memoized fetch.
+#[derive(Clone, Copy, Debug)]
+pub enum Revalidate {
+    /// Only `invalidate_cached_fetch` clears the entry; it's otherwise served forever.
+    Never,
+    /// Once `fetched_at` is older than this, the next call still gets the stale result, but also
+    /// kicks off a re-fetch in the background so later calls see a fresh one.
+    After(Duration),
+}
+
+lazy_static! {
+    static ref FETCH_CACHE: Mutex<HashMap<String, CachedFetch>> = Mutex::new(HashMap::new());
+}
+
+/// Returns the memoized result for `key`, calling `fetch` on a miss.
+///
+/// On a fresh hit, `fetch` is never called. On a stale hit (per `policy`), the stale result is
+/// returned immediately and `fetch` is re-run in the background to refresh the cache for the
+/// *next* call -- the current call still gets the old result rather than waiting on a re-fetch.
+pub async fn memoize_fetch<F, Fut>(key: &str, policy: Revalidate, fetch: F) -> String
+where
+    F: Fn() -> Fut + Send + 'static,
+    Fut: Future<Output = String> + Send,
+{
+    let stale = {
+        let cache = FETCH_CACHE.lock().unwrap();
+        cache.get(key).cloned()
+    };
+
+    match stale {
+        Some(entry) if !is_stale(&entry, policy) => entry.payload,
+        Some(entry) => {
+            let key = key.to_string();
+            tokio::spawn(async move {
+                let payload = fetch().await;
+                FETCH_CACHE.lock().unwrap().insert(
+                    key,
+                    CachedFetch {
+                        payload,
+                        fetched_at: Instant::now(),
+                    },
+                );
+            });
+            entry.payload
+        }
+        None => {
+            let payload = fetch().await;
+            let entry = CachedFetch {
+                payload,
+                fetched_at: Instant::now(),
+            };
+            FETCH_CACHE
+                .lock()
+                .unwrap()
+                .insert(key.to_string(), entry.clone());
+            entry.payload
+        }
+    }
+}
+
+fn is_stale(entry: &CachedFetch, policy: Revalidate) -> bool {
+    match policy {
+        Revalidate::Never => false,
+        Revalidate::After(max_age) => entry.fetched_at.elapsed() >= max_age,
+    }
+}
+
+/// Explicitly drop the cached result for `key`, e.g. after a server function mutates the data
+/// `key` was fetched from. The next call for `key` fetches from scratch.
+pub fn invalidate_cached_fetch(key: &str) {
+    FETCH_CACHE.lock().unwrap().remove(key);
+}
+
+/// Pre-fetches every key produced from `known_params` (e.g. every known post id) so the cache is
+/// warm before the first real call comes in, rather than paying the fetch cost on whichever call
+/// happens to arrive first.
+pub async fn prewarm_known_fetches<P, F, Fut>(
+    known_params: Vec<P>,
+    key_for: impl Fn(&P) -> String,
+    fetch: F,
+) where
+    F: Fn(&P) -> Fut,
+    Fut: Future<Output = String>,
+{
+    for params in &known_params {
+        let key = key_for(params);
+        let payload = fetch(params).await;
+        FETCH_CACHE.lock().unwrap().insert(
+            key,
+            CachedFetch {
+                payload,
+                fetched_at: Instant::now(),
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry_fetched_ago(age: Duration) -> CachedFetch {
+        CachedFetch {
+            payload: String::new(),
+            fetched_at: Instant::now() - age,
+        }
+    }
+
+    #[test]
+    fn never_is_never_stale() {
+        let entry = entry_fetched_ago(Duration::from_secs(60 * 60));
+        assert!(!is_stale(&entry, Revalidate::Never));
+    }
+
+    #[test]
+    fn after_is_fresh_within_the_window() {
+        let entry = entry_fetched_ago(Duration::from_millis(1));
+        assert!(!is_stale(&entry, Revalidate::After(Duration::from_secs(60))));
+    }
+
+    #[test]
+    fn after_is_stale_once_the_window_has_passed() {
+        let entry = entry_fetched_ago(Duration::from_secs(61));
+        assert!(is_stale(&entry, Revalidate::After(Duration::from_secs(60))));
+    }
+
+    #[tokio::test]
+    async fn fresh_hit_does_not_call_fetch_again() {
+        use std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        };
+
+        let key = "/test/fresh-hit-does-not-refetch";
+        invalidate_cached_fetch(key);
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        for _ in 0..2 {
+            let calls = calls.clone();
+            let payload = memoize_fetch(key, Revalidate::Never, move || {
+                let calls = calls.clone();
+                async move {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    "fetched".to_string()
+                }
+            })
+            .await;
+            assert_eq!(payload, "fetched");
+        }
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn miss_calls_fetch_once() {
+        use std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        };
+
+        let key = "/test/miss-calls-fetch-once";
+        invalidate_cached_fetch(key);
+        let calls = Arc::new(AtomicUsize::new(0));
+        let calls_for_fetch = calls.clone();
+
+        let payload = memoize_fetch(key, Revalidate::Never, move || {
+            let calls = calls_for_fetch.clone();
+            async move {
+                calls.fetch_add(1, Ordering::SeqCst);
+                "fetched".to_string()
+            }
+        })
+        .await;
+
+        assert_eq!(payload, "fetched");
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,74 @@
+//! Defends against `</script>`/`<!--` breakout when a resource's serialized payload is inlined
+//! into the SSR response's hydration `<script>` tag.
+//!
+//! A resource's value can be attacker-controlled data -- e.g. `Post::content` containing the
+//! literal bytes `</script><script>alert(1)</script>` -- and if that ends up verbatim inside the
+//! page's inline hydration `<script>`, it can break out of the script context and inject markup.
+//! [`escape_script_breakout`] rewrites the handful of byte sequences that matter for that (`<`,
+//! `>`, `&`, and the `-->` comment terminator) to their `\uXXXX` escapes, so the *parsed* value
+//! is unchanged but the *raw bytes* can't break out of the surrounding `<script>` element.
+//!
+//! `Resource::new_serde` itself still serializes straight to JSON with no such escaping, and
+//! that's internal to `leptos_server`, not something this crate can patch. So instead of relying
+//! on it, `HomePage`'s `posts` resource and `Post`'s `post_resource` (in `app.rs`) both go
+//! through [`crate::codec::JsonCodec`], which calls this function on every value it encodes --
+//! see that module for the wiring.
+
+/// Escapes the HTML-significant byte sequences in `encoded` so it's safe to inline inside a
+/// `<script>` element, without changing what it deserializes to.
+pub fn escape_script_breakout(encoded: &str) -> String {
+    let mut escaped = String::with_capacity(encoded.len());
+    let mut chars = encoded.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '<' => escaped.push_str("\\u003c"),
+            '>' => escaped.push_str("\\u003e"),
+            '&' => escaped.push_str("\\u0026"),
+            // A lone `-` is harmless; it's only the start of a `-->` sequence (which can close
+            // a surrounding HTML comment) that needs breaking up, so only escape it then.
+            '-' if starts_with(chars.clone(), "->") => escaped.push_str("\\u002d"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn starts_with(mut chars: std::iter::Peekable<std::str::Chars>, needle: &str) -> bool {
+    needle.chars().all(|expected| chars.next() == Some(expected))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roundtrip(original: &str) -> (String, String) {
+        let json = serde_json::to_string(&original).unwrap();
+        let escaped = escape_script_breakout(&json);
+        let decoded: String = serde_json::from_str(&escaped).unwrap();
+        (escaped, decoded)
+    }
+
+    #[test]
+    fn escapes_script_close_tag_breakout() {
+        let content = "</script><script>alert(1)</script>";
+        let (escaped, decoded) = roundtrip(content);
+
+        assert!(!escaped.contains("</script>"));
+        assert!(!escaped.contains("<script>"));
+        assert_eq!(decoded, content);
+    }
+
+    #[test]
+    fn escapes_html_comment_terminator() {
+        let (escaped, decoded) = roundtrip("-->malicious");
+
+        assert!(!escaped.contains("-->"));
+        assert_eq!(decoded, "-->malicious");
+    }
+
+    #[test]
+    fn leaves_plain_content_byte_identical_after_roundtrip() {
+        let (_, decoded) = roundtrip("just a normal post body");
+        assert_eq!(decoded, "just a normal post body");
+    }
+}
@@ -3,6 +3,15 @@ pub mod app;
 #[cfg(feature = "ssr")]
 pub mod fallback;
 
+pub mod codec;
+pub mod diagnostic;
+pub mod html_escape;
+pub mod protected_route;
+pub mod transition;
+
+#[cfg(feature = "ssr")]
+pub mod fetch_cache;
+
 #[cfg(feature = "hydrate")]
 #[wasm_bindgen::prelude::wasm_bindgen]
 pub fn hydrate() {
@@ -0,0 +1,58 @@
+use std::future::Future;
+
+use leptos::either::Either;
+use leptos::prelude::*;
+use leptos_router::components::Redirect;
+
+/// An auth-gated page: awaits `condition` before deciding whether to render `view` or redirect
+/// to `redirect_path`.
+///
+/// Use it as a route's `view`, the same way `Post` is used as `view=Post` in `app.rs` -- wrap
+/// whatever you'd otherwise pass to `view` in a `<ProtectedRoute>`:
+///
+/// ```ignore
+/// <Route
+///     path=StaticSegment("admin")
+///     view=|| view! {
+///         <ProtectedRoute
+///             condition=|| async move { is_admin().await.unwrap_or(false) }
+///             redirect_path="/"
+///             view=AdminPage
+///         />
+///     }
+///     ssr=SsrMode::Async
+/// />
+/// ```
+///
+/// Because the guard is driven by a `Suspend`-wrapped future, just like every other async value
+/// in this app, it participates in the same streaming/blocking lifecycle as the page's other
+/// resources: under `SsrMode::Async`/`InOrder` the response isn't committed until `condition`
+/// (and everything else the page suspends on) has resolved, so an unauthorized request gets a
+/// real redirect status rather than a flash of protected content. During hydration/CSR the same
+/// future just resolves after the initial paint, and `<Redirect>` performs a client-side
+/// navigation to `redirect_path` instead.
+#[component]
+pub fn ProtectedRoute<Cond, Fut, View, IV>(
+    /// Resolves to `true` if the current user may view this route.
+    condition: Cond,
+    /// Where to send the user if `condition` resolves to `false`.
+    redirect_path: &'static str,
+    /// The protected content, rendered once `condition` resolves to `true`.
+    view: View,
+) -> impl IntoView
+where
+    Cond: Fn() -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = bool> + Send + 'static,
+    View: Fn() -> IV + Send + 'static,
+    IV: IntoView + 'static,
+{
+    let guarded = Suspend(async move {
+        if condition().await {
+            Either::Left(view())
+        } else {
+            Either::Right(view! { <Redirect path=redirect_path/> })
+        }
+    });
+
+    view! { <Suspense fallback=|| ()>{guarded}</Suspense> }
+}
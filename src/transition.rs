@@ -0,0 +1,80 @@
+//! `<Transition>`: like `<Suspense>`, but on a refetch it keeps the previously rendered
+//! children on screen instead of showing `fallback` again, swapping in the new content once
+//! it's ready.
+//!
+//! A `<Suspense>` shows `fallback` for its *entire* children for as long as any `Suspend` inside
+//! it is pending -- that's correct for a first load, but `resource.get()` going pending again on
+//! every refetch means a naive "await the resource inside the same `<Suspense>` that also shows
+//! `resource.get()`" would flip back to `fallback` on every refetch too, which is the exact flash
+//! this component exists to avoid. So the two cases are split instead of sharing a boundary:
+//! once `resource.get()` has resolved at least once, children are rendered directly from it
+//! (outside any `<Suspense>`), and it keeps returning the last-resolved value while a refetch is
+//! in flight, so there's always something to show. Only before the first resolution -- where
+//! there's no stale value to fall back to -- does this defer to a real `<Suspense>`, which
+//! blocks the nearest boundary (and, on the server, the response itself) exactly like it would
+//! on its own.
+
+use leptos::prelude::*;
+
+/// Whether a `<Transition>` is currently holding stale content on screen while a refetch runs.
+/// Read this from inside the `<Transition>`'s children to e.g. dim the UI during a transition.
+#[derive(Copy, Clone)]
+pub struct TransitionPending(Signal<bool>);
+
+impl TransitionPending {
+    pub fn get(&self) -> bool {
+        self.0.get()
+    }
+}
+
+/// Reads the nearest ancestor `<Transition>`'s pending state; `false` if there isn't one.
+pub fn use_transition_pending() -> Signal<bool> {
+    match use_context::<TransitionPending>() {
+        Some(pending) => pending.0,
+        None => Signal::from(false),
+    }
+}
+
+#[component]
+pub fn Transition<T, FallbackFn, Fallback, ChildrenFn, IV>(
+    /// The resource whose last-resolved value should stay on screen across refetches.
+    resource: Resource<T>,
+    fallback: FallbackFn,
+    children: ChildrenFn,
+) -> impl IntoView
+where
+    T: Clone + Send + Sync + 'static,
+    FallbackFn: Fn() -> Fallback + Clone + Send + 'static,
+    Fallback: IntoView + 'static,
+    ChildrenFn: Fn(T) -> IV + Clone + Send + 'static,
+    IV: IntoView + 'static,
+{
+    provide_context(TransitionPending(resource.loading()));
+
+    view! {
+        {move || {
+            let children = children.clone();
+            match resource.get() {
+                // Already has a resolved value (first load done, or a stale one from before
+                // this refetch) -- render it directly, with no `<Suspense>` in the way that a
+                // pending refetch could flip back to `fallback`.
+                Some(value) => children(value).into_any(),
+                // Nothing resolved yet; there's no stale content to hold onto, so this is the
+                // one case that actually needs to block on the resource like a plain
+                // `<Suspense>` would.
+                None => {
+                    let fallback = fallback.clone();
+                    view! {
+                        <Suspense fallback=fallback>
+                            {move || {
+                                let children = children.clone();
+                                Suspend(async move { children(resource.await).into_any() })
+                            }}
+                        </Suspense>
+                    }
+                    .into_any()
+                }
+            }
+        }}
+    }
+}